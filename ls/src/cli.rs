@@ -0,0 +1,191 @@
+use clap::{
+    crate_authors, crate_description, crate_name, crate_version, App, AppSettings::ColoredHelp, Arg,
+};
+
+pub(crate) fn create_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(crate_name!())
+        .version(crate_version!())
+        .author(crate_authors!())
+        .about(crate_description!())
+        .help_message("Display help information.")
+        .version_message("Display version information.")
+        .help_short("?")
+        .settings(&[ColoredHelp])
+        .arg(
+            Arg::with_name("FILE")
+                .help("File(s) to list.")
+                .multiple(true)
+                .default_value("."),
+        )
+        .arg(
+            Arg::with_name("all")
+                .help("Do not ignore entries starting with .")
+                .long("all")
+                .short("a"),
+        )
+        .arg(
+            Arg::with_name("almost_all")
+                .help("Do not list implied . and ..")
+                .long("almost-all")
+                .short("A"),
+        )
+        .arg(
+            Arg::with_name("long")
+                .help("Use a long listing format.")
+                .long("long")
+                .short("l"),
+        )
+        .arg(
+            Arg::with_name("reverse")
+                .help("Reverse order while sorting.")
+                .long("reverse")
+                .short("r"),
+        )
+        .arg(
+            Arg::with_name("no_sort")
+                .help("Do not sort; list entries in directory order.")
+                .long("no-sort")
+                .short("f"),
+        )
+        .arg(
+            Arg::with_name("sort_size")
+                .help("Sort by file size, largest first.")
+                .long("sort-size")
+                .short("S"),
+        )
+        .arg(
+            Arg::with_name("time")
+                .help("Sort by modification time, newest first.")
+                .long("time")
+                .short("t"),
+        )
+        .arg(
+            Arg::with_name("last_accessed")
+                .help("With -t, sort by and show last access time instead.")
+                .long("last-accessed")
+                .short("u")
+                .requires("time"),
+        )
+        .arg(
+            Arg::with_name("comma_separate")
+                .help("List entries separated by commas.")
+                .long("comma-separate")
+                .short("m"),
+        )
+        .arg(
+            Arg::with_name("inode")
+                .help("Print the index number of each file.")
+                .long("inode")
+                .short("i"),
+        )
+        .arg(
+            Arg::with_name("size")
+                .help("Print the allocated size of each file, in blocks.")
+                .long("size")
+                .short("s"),
+        )
+        .arg(
+            Arg::with_name("no_owner")
+                .help("Do not print group names in a long listing.")
+                .long("no-owner")
+                .short("g"),
+        )
+        .arg(
+            Arg::with_name("color")
+                .help("Colorize the output.")
+                .long("color")
+                .takes_value(true)
+                .possible_values(&["always", "auto", "never"])
+                .default_value("auto"),
+        )
+        .arg(
+            Arg::with_name("human_readable")
+                .help("Print sizes in human-readable units (e.g. 1.0K, 234M, 2.3G).")
+                .long("human-readable")
+                .short("h")
+                .conflicts_with("block_size"),
+        )
+        .arg(
+            Arg::with_name("si")
+                .help("Like -h, but use powers of 1000 instead of 1024.")
+                .long("si")
+                .conflicts_with("block_size"),
+        )
+        .arg(
+            Arg::with_name("block_size")
+                .help("Scale sizes by N before printing, e.g. --block-size=1K.")
+                .long("block-size")
+                .takes_value(true)
+                .value_name("N"),
+        )
+        .arg(
+            Arg::with_name("recursive")
+                .help("List subdirectories recursively.")
+                .long("recursive")
+                .short("R"),
+        )
+        .arg(
+            Arg::with_name("depth")
+                .help("Limit recursion (with -R or --tree) to N levels deep.")
+                .long("depth")
+                .takes_value(true)
+                .value_name("N"),
+        )
+        .arg(
+            Arg::with_name("tree")
+                .help("Render subdirectories as an indented tree instead of per-directory blocks.")
+                .long("tree"),
+        )
+        .arg(
+            Arg::with_name("threads")
+                .help("Number of worker threads used to stat entries. Defaults to available parallelism.")
+                .long("threads")
+                .takes_value(true)
+                .value_name("N"),
+        )
+        .arg(
+            Arg::with_name("one_per_line")
+                .help("List one entry per line.")
+                .long("one-per-line")
+                .short("1"),
+        )
+        .arg(
+            Arg::with_name("across")
+                .help("List entries by lines instead of by columns.")
+                .long("across")
+                .short("x"),
+        )
+        .arg(
+            Arg::with_name("columns")
+                .help("Force multi-column output even when stdout isn't a terminal.")
+                .long("columns")
+                .short("C"),
+        )
+        .arg(
+            Arg::with_name("git")
+                .help("In long format, show each entry's Git working-tree status.")
+                .long("git")
+                .requires("long"),
+        )
+        .arg(
+            Arg::with_name("ignore")
+                .help("Do not list entries matching PATTERN, unless -a or -A is given. May be repeated.")
+                .long("ignore")
+                .takes_value(true)
+                .value_name("PATTERN")
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            // dutree uses `-x` for this, but that short flag is already taken
+            // by `across` above, so `exclude` gets `-X` instead.
+            Arg::with_name("exclude")
+                .help("Do not list entries matching PATTERN, even under -a or -A. May be repeated.")
+                .long("exclude")
+                .short("X")
+                .takes_value(true)
+                .value_name("PATTERN")
+                .multiple(true)
+                .number_of_values(1),
+        )
+}
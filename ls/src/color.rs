@@ -0,0 +1,213 @@
+use std::collections::HashMap;
+use std::env;
+
+/// The category a file is classified into for the purposes of picking an
+/// output color. Mirrors the scheme used by tools like `exa` and `lsd`:
+/// entries are first classified by what they *are* (directory, symlink,
+/// executable, device/special file) and only fall back to an
+/// extension-based guess (image, video, document, ...) for regular files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum Category {
+    Directory,
+    Symlink,
+    Executable,
+    Special,
+    Image,
+    Video,
+    Music,
+    Lossless,
+    Crypto,
+    Document,
+    Compressed,
+    Temp,
+    Compiled,
+    Normal,
+}
+
+/// Extensions that map to a non-default category, checked in order against
+/// a lowercased file extension.
+const EXTENSION_CATEGORIES: &[(&str, Category)] = &[
+    ("png", Category::Image),
+    ("jpg", Category::Image),
+    ("jpeg", Category::Image),
+    ("gif", Category::Image),
+    ("bmp", Category::Image),
+    ("svg", Category::Image),
+    ("webp", Category::Image),
+    ("ico", Category::Image),
+    ("mp4", Category::Video),
+    ("mkv", Category::Video),
+    ("mov", Category::Video),
+    ("avi", Category::Video),
+    ("webm", Category::Video),
+    ("mp3", Category::Music),
+    ("ogg", Category::Music),
+    ("m4a", Category::Music),
+    ("aac", Category::Music),
+    ("flac", Category::Lossless),
+    ("wav", Category::Lossless),
+    ("ape", Category::Lossless),
+    ("alac", Category::Lossless),
+    ("asc", Category::Crypto),
+    ("gpg", Category::Crypto),
+    ("pgp", Category::Crypto),
+    ("pem", Category::Crypto),
+    ("key", Category::Crypto),
+    ("pdf", Category::Document),
+    ("doc", Category::Document),
+    ("docx", Category::Document),
+    ("odt", Category::Document),
+    ("md", Category::Document),
+    ("rst", Category::Document),
+    ("txt", Category::Document),
+    ("zip", Category::Compressed),
+    ("tar", Category::Compressed),
+    ("gz", Category::Compressed),
+    ("bz2", Category::Compressed),
+    ("xz", Category::Compressed),
+    ("7z", Category::Compressed),
+    ("rar", Category::Compressed),
+    ("tgz", Category::Compressed),
+    ("tmp", Category::Temp),
+    ("swp", Category::Temp),
+    ("bak", Category::Temp),
+    ("o", Category::Compiled),
+    ("so", Category::Compiled),
+    ("class", Category::Compiled),
+    ("pyc", Category::Compiled),
+    ("rlib", Category::Compiled),
+];
+
+/// Look up the category implied by a file extension alone. Used as the
+/// fallback once directory/symlink/executable/special have been ruled out.
+pub(crate) fn category_for_extension(extension: &str) -> Category {
+    let extension = extension.to_lowercase();
+
+    EXTENSION_CATEGORIES
+        .iter()
+        .find(|(ext, _)| *ext == extension)
+        .map(|(_, category)| *category)
+        .unwrap_or(Category::Normal)
+}
+
+/// When colorization should be applied, following the GNU `--color` flag
+/// semantics: `always` forces it on, `never` forces it off and `auto`
+/// colorizes only when stdout is a TTY.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ColorMode {
+    Always,
+    Auto,
+    Never,
+}
+
+impl ColorMode {
+    pub(crate) fn from_str(value: &str) -> Self {
+        match value {
+            "always" => ColorMode::Always,
+            "never" => ColorMode::Never,
+            _ => ColorMode::Auto,
+        }
+    }
+
+    /// Resolve against whether the output stream is a TTY.
+    pub(crate) fn enabled(self, stdout_is_tty: bool) -> bool {
+        match self {
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+            ColorMode::Auto => stdout_is_tty,
+        }
+    }
+}
+
+/// The built-in fallback colors, used for any category `LS_COLORS` doesn't
+/// override. Codes are SGR parameters, e.g. `"01;34"` for bold blue.
+fn default_code(category: Category) -> &'static str {
+    match category {
+        Category::Directory => "01;34",
+        Category::Symlink => "01;36",
+        Category::Executable => "01;32",
+        Category::Special => "01;33",
+        Category::Image => "01;35",
+        Category::Video => "01;35",
+        Category::Music => "00;36",
+        Category::Lossless => "00;36",
+        Category::Crypto => "00;31",
+        Category::Document => "00;00",
+        Category::Compressed => "01;31",
+        Category::Temp => "00;37",
+        Category::Compiled => "00;33",
+        Category::Normal => "00;00",
+    }
+}
+
+/// A set of colors to paint file names with, built from `LS_COLORS` when set
+/// and falling back to [`default_code`] otherwise.
+pub(crate) struct Palette {
+    enabled: bool,
+    category_codes: HashMap<Category, String>,
+    extension_codes: HashMap<String, String>,
+}
+
+impl Palette {
+    /// Build a palette honoring `LS_COLORS`, enabled according to `mode` and
+    /// whether stdout is a TTY.
+    pub(crate) fn new(mode: ColorMode, stdout_is_tty: bool) -> Self {
+        let mut palette = Palette {
+            enabled: mode.enabled(stdout_is_tty),
+            category_codes: HashMap::new(),
+            extension_codes: HashMap::new(),
+        };
+
+        if let Ok(ls_colors) = env::var("LS_COLORS") {
+            palette.load(&ls_colors);
+        }
+
+        palette
+    }
+
+    fn load(&mut self, ls_colors: &str) {
+        for entry in ls_colors.split(':') {
+            let mut parts = entry.splitn(2, '=');
+            let (key, code) = match (parts.next(), parts.next()) {
+                (Some(key), Some(code)) if !key.is_empty() && !code.is_empty() => (key, code),
+                _ => continue,
+            };
+
+            match key {
+                "di" => self.category_codes.insert(Category::Directory, code.to_string()),
+                "ln" => self.category_codes.insert(Category::Symlink, code.to_string()),
+                "ex" => self.category_codes.insert(Category::Executable, code.to_string()),
+                _ if key.starts_with("*.") => {
+                    self.extension_codes.insert(key[2..].to_lowercase(), code.to_string())
+                },
+                _ => None,
+            };
+        }
+    }
+
+    /// Wrap `name` in the ANSI style matching `category`/`extension`, or
+    /// return it unchanged when coloring is disabled. Extension-based colors
+    /// only apply to regular, non-executable files, matching GNU `ls`: a
+    /// directory or symlink keeps its category color even if its name happens
+    /// to end in `.doc` or similar.
+    pub(crate) fn paint(&self, name: &str, category: Category, extension: Option<&str>) -> String {
+        if !self.enabled {
+            return name.to_string();
+        }
+
+        let by_type = matches!(
+            category,
+            Category::Directory | Category::Symlink | Category::Executable | Category::Special
+        );
+
+        let code = if !by_type {
+            extension.map(str::to_lowercase).and_then(|ext| self.extension_codes.get(&ext).cloned())
+        } else {
+            None
+        }
+        .or_else(|| self.category_codes.get(&category).cloned())
+        .unwrap_or_else(|| default_code(category).to_string());
+
+        format!("\x1b[{}m{}\x1b[0m", code, name)
+    }
+}
@@ -0,0 +1,148 @@
+use std::fs;
+use std::io;
+use std::os::unix::fs::{FileTypeExt, MetadataExt};
+use std::path::PathBuf;
+
+use chrono::{DateTime, Local};
+use users::{get_group_by_gid, get_user_by_uid};
+
+use crate::color::{self, Category, Palette};
+use crate::flags::Flags;
+use crate::size;
+
+/// A single entry being listed, along with the metadata needed to print it
+/// in either the default or long (`-l`) format.
+pub(crate) struct File {
+    pub name: String,
+    pub path: PathBuf,
+    pub metadata: fs::Metadata,
+    flags: Flags,
+}
+
+impl File {
+    /// Build a `File` from a path on disk, reading its metadata without
+    /// following a trailing symlink so `-l` can report it as a link.
+    pub(crate) fn from(path: PathBuf, flags: Flags) -> io::Result<Self> {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.to_string_lossy().into_owned());
+
+        Self::build(name, path, flags)
+    }
+
+    /// Build a `File` for a synthetic entry (`.` or `..`) that should be
+    /// displayed under the given `name` rather than the path's own file name.
+    pub(crate) fn from_name(name: String, path: PathBuf, flags: Flags) -> io::Result<Self> {
+        Self::build(name, path, flags)
+    }
+
+    fn build(name: String, path: PathBuf, flags: Flags) -> io::Result<Self> {
+        let metadata = path.symlink_metadata()?;
+
+        Ok(File { name, path, metadata, flags })
+    }
+
+    /// Whether a file name should be hidden unless `-a`/`-A` was given.
+    pub(crate) fn is_hidden(name: &str) -> bool { name.starts_with('.') }
+
+    /// Classify this entry for the purposes of colorizing its name.
+    fn category(&self) -> Category {
+        let file_type = self.metadata.file_type();
+
+        if file_type.is_dir() {
+            Category::Directory
+        } else if file_type.is_symlink() {
+            Category::Symlink
+        } else if file_type.is_char_device()
+            || file_type.is_block_device()
+            || file_type.is_fifo()
+            || file_type.is_socket()
+        {
+            Category::Special
+        } else if self.metadata.mode() & 0o111 != 0 {
+            Category::Executable
+        } else {
+            self.path
+                .extension()
+                .map(|ext| color::category_for_extension(&ext.to_string_lossy()))
+                .unwrap_or(Category::Normal)
+        }
+    }
+
+    /// The file name as it should be printed, colorized according to
+    /// `palette`.
+    pub(crate) fn file_name(&self, palette: &Palette) -> String {
+        let extension = self.path.extension().map(|ext| ext.to_string_lossy().into_owned());
+
+        palette.paint(&self.name, self.category(), extension.as_deref())
+    }
+
+    /// The inode number, for `-i`.
+    pub(crate) fn inode(&self) -> String { self.metadata.ino().to_string() }
+
+    /// The number of 512-byte blocks allocated, for `-s`.
+    pub(crate) fn blocks(&self) -> String { self.metadata.blocks().to_string() }
+
+    /// The number of hard links to this entry.
+    pub(crate) fn hard_links(&self) -> String { self.metadata.nlink().to_string() }
+
+    /// The `rwxr-xr-x`-style permissions string, including the leading file
+    /// type character.
+    pub(crate) fn permissions(&self) -> String {
+        let file_type = self.metadata.file_type();
+
+        let kind = if file_type.is_dir() {
+            'd'
+        } else if file_type.is_symlink() {
+            'l'
+        } else if file_type.is_char_device() {
+            'c'
+        } else if file_type.is_block_device() {
+            'b'
+        } else if file_type.is_fifo() {
+            'p'
+        } else if file_type.is_socket() {
+            's'
+        } else {
+            '-'
+        };
+
+        let mode = self.metadata.mode();
+        let triplet = |shift: u32| -> String {
+            let read = if mode & (0o4 << shift) != 0 { 'r' } else { '-' };
+            let write = if mode & (0o2 << shift) != 0 { 'w' } else { '-' };
+            let execute = if mode & (0o1 << shift) != 0 { 'x' } else { '-' };
+            format!("{}{}{}", read, write, execute)
+        };
+
+        format!("{}{}{}{}", kind, triplet(6), triplet(3), triplet(0))
+    }
+
+    /// The owning user's name, or an error if the uid doesn't resolve.
+    pub(crate) fn user(&self) -> Result<String, io::Error> {
+        get_user_by_uid(self.metadata.uid())
+            .map(|user| user.name().to_string_lossy().into_owned())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "failed to resolve user"))
+    }
+
+    /// The owning group's name, or an error if the gid doesn't resolve.
+    pub(crate) fn group(&self) -> Result<String, io::Error> {
+        get_group_by_gid(self.metadata.gid())
+            .map(|group| group.name().to_string_lossy().into_owned())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "failed to resolve group"))
+    }
+
+    /// The file size, formatted according to `-h`/`--si`/`--block-size`.
+    pub(crate) fn size(&self) -> String { size::format_size(self.metadata.len(), self.flags.size_format) }
+
+    /// The modification time (or, with `-u`, the last access time), formatted
+    /// the way GNU `ls -l` does.
+    pub(crate) fn time(&self) -> io::Result<String> {
+        let time = if self.flags.last_accessed { self.metadata.accessed()? } else { self.metadata.modified()? };
+
+        let time: DateTime<Local> = time.into();
+
+        Ok(time.format("%b %e %H:%M").to_string())
+    }
+}
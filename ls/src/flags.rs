@@ -0,0 +1,129 @@
+use std::thread;
+
+use clap::ArgMatches;
+use glob::Pattern;
+
+use crate::color::ColorMode;
+use crate::size::{self, SizeFormat};
+
+/// Holds the parsed command-line flags, threaded through to `File` and the
+/// print functions so they don't each have to re-query `ArgMatches`. Carries
+/// the compiled `--ignore`/`--exclude` globs, so it is `Clone` rather than
+/// `Copy`.
+#[derive(Clone)]
+pub(crate) struct Flags {
+    pub all: bool,
+    pub almost_all: bool,
+    pub long: bool,
+    pub reverse: bool,
+    pub no_sort: bool,
+    pub sort_size: bool,
+    pub time: bool,
+    pub last_accessed: bool,
+    pub comma_separate: bool,
+    pub inode: bool,
+    pub size: bool,
+    pub no_owner: bool,
+    pub color: ColorMode,
+    pub size_format: SizeFormat,
+    pub recursive: bool,
+    pub depth: Option<usize>,
+    pub tree: bool,
+    pub threads: usize,
+    pub one_per_line: bool,
+    pub across: bool,
+    pub columns: bool,
+    pub git: bool,
+    ignore: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+}
+
+impl Flags {
+    pub(crate) fn from_matches(matches: &ArgMatches) -> Self {
+        Flags {
+            all: matches.is_present("all"),
+            almost_all: matches.is_present("almost_all"),
+            long: matches.is_present("long"),
+            reverse: matches.is_present("reverse"),
+            no_sort: matches.is_present("no_sort"),
+            sort_size: matches.is_present("sort_size"),
+            time: matches.is_present("time"),
+            last_accessed: matches.is_present("last_accessed"),
+            comma_separate: matches.is_present("comma_separate"),
+            inode: matches.is_present("inode"),
+            size: matches.is_present("size"),
+            no_owner: matches.is_present("no_owner"),
+            color: matches
+                .value_of("color")
+                .map(ColorMode::from_str)
+                .unwrap_or(ColorMode::Auto),
+            size_format: parse_block_size_flag(matches.value_of("block_size")).unwrap_or_else(|| {
+                if matches.is_present("human_readable") || matches.is_present("si") {
+                    SizeFormat::HumanReadable { si: matches.is_present("si") }
+                } else {
+                    SizeFormat::Bytes
+                }
+            }),
+            recursive: matches.is_present("recursive"),
+            depth: matches.value_of("depth").and_then(|depth| depth.parse().ok()),
+            tree: matches.is_present("tree"),
+            threads: matches
+                .value_of("threads")
+                .and_then(|threads| threads.parse().ok())
+                .unwrap_or_else(|| thread::available_parallelism().map(|n| n.get()).unwrap_or(1)),
+            one_per_line: matches.is_present("one_per_line"),
+            across: matches.is_present("across"),
+            columns: matches.is_present("columns"),
+            git: matches.is_present("git"),
+            ignore: compile_patterns(matches.values_of("ignore")),
+            exclude: compile_patterns(matches.values_of("exclude")),
+        }
+    }
+
+    /// Whether `.`-prefixed entries should be included in the listing.
+    pub(crate) fn show_hidden(&self) -> bool { self.all || self.almost_all }
+
+    /// Whether the long (`-l`) format should be used.
+    pub(crate) fn show_list(&self) -> bool { self.long }
+
+    /// Whether `name` matches a `--ignore` pattern, hidden unless `-a`/`-A`
+    /// was given.
+    pub(crate) fn is_ignored(&self, name: &str) -> bool {
+        self.ignore.iter().any(|pattern| pattern.matches(name))
+    }
+
+    /// Whether `name` matches an `--exclude` pattern, hidden unconditionally.
+    pub(crate) fn is_excluded(&self, name: &str) -> bool {
+        self.exclude.iter().any(|pattern| pattern.matches(name))
+    }
+}
+
+/// Parse a `--block-size` value, warning and falling back to the default
+/// size format (rather than silently dropping the flag) if it's malformed.
+fn parse_block_size_flag(value: Option<&str>) -> Option<SizeFormat> {
+    let value = value?;
+
+    match size::parse_block_size(value) {
+        Some(block_size) => Some(SizeFormat::BlockSize(block_size)),
+        None => {
+            eprintln!("ls: invalid --block-size value '{}'", value);
+            None
+        },
+    }
+}
+
+/// Compile each value of a repeatable `--ignore`/`--exclude` argument into a
+/// glob matcher, warning about and dropping any that fail to parse.
+fn compile_patterns(values: Option<clap::Values>) -> Vec<Pattern> {
+    values
+        .into_iter()
+        .flatten()
+        .filter_map(|pattern| match Pattern::new(pattern) {
+            Ok(pattern) => Some(pattern),
+            Err(err) => {
+                eprintln!("ls: invalid pattern '{}': {}", pattern, err);
+                None
+            },
+        })
+        .collect()
+}
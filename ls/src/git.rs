@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use git2::{Repository, Status, StatusOptions};
+
+/// Working-tree status for every changed path in a repository, computed
+/// once per path given on the command line (via [`GitCache::for_directory`])
+/// and shared with the subdirectories listed under it by `-R`, so `--git`
+/// doesn't reopen and re-walk the repository for every entry printed.
+pub(crate) struct GitCache {
+    workdir: PathBuf,
+    statuses: HashMap<PathBuf, String>,
+}
+
+impl GitCache {
+    /// Open the repository containing `directory`, if any, and compute a
+    /// two-character status code for every path it reports as changed.
+    /// Ignored paths are only included when `include_ignored` is set.
+    pub(crate) fn for_directory(directory: &Path, include_ignored: bool) -> Option<Self> {
+        let repo = Repository::discover(directory).ok()?;
+        let workdir = repo.workdir()?.to_path_buf();
+
+        let mut options = StatusOptions::new();
+        options.include_untracked(true).recurse_untracked_dirs(true).include_ignored(include_ignored);
+
+        let statuses = repo.statuses(Some(&mut options)).ok()?;
+
+        let statuses = statuses
+            .iter()
+            .filter_map(|entry| entry.path().map(|path| (workdir.join(path), format_status(entry.status()))))
+            .collect();
+
+        Some(GitCache { workdir, statuses })
+    }
+
+    /// The status code for `path`: a placeholder dash pair for an untouched
+    /// tracked path, or `None` if `path` isn't inside this repository.
+    /// `path` is canonicalized first since entries are read from whatever
+    /// (often relative) directory the user passed, while `workdir` and the
+    /// cached keys are always absolute.
+    pub(crate) fn status(&self, path: &Path) -> Option<&str> {
+        let path = path.canonicalize().ok()?;
+
+        if !path.starts_with(&self.workdir) {
+            return None;
+        }
+
+        Some(self.statuses.get(&path).map(String::as_str).unwrap_or("--"))
+    }
+}
+
+/// Render a status as the compact two-character code exa uses: staged
+/// change, then unstaged change, `-` for neither.
+fn format_status(status: Status) -> String {
+    let staged = if status.is_index_new() {
+        "A"
+    } else if status.is_index_modified() {
+        "M"
+    } else if status.is_index_deleted() {
+        "D"
+    } else if status.is_index_renamed() {
+        "R"
+    } else if status.is_index_typechange() {
+        "T"
+    } else {
+        "-"
+    };
+
+    let unstaged = if status.is_wt_new() {
+        "A"
+    } else if status.is_wt_modified() {
+        "M"
+    } else if status.is_wt_deleted() {
+        "D"
+    } else if status.is_wt_renamed() {
+        "R"
+    } else if status.is_wt_typechange() {
+        "T"
+    } else if status.is_ignored() {
+        "I"
+    } else {
+        "-"
+    };
+
+    format!("{}{}", staged, unstaged)
+}
@@ -1,21 +1,33 @@
 use std::{
     fs,
     io::{self, Write},
-    path, process,
+    path::{self, Path},
+    process,
     string::String,
     time::SystemTime,
 };
 
 use pad::{Alignment, PadStr};
 
+extern crate atty;
 extern crate chrono;
+extern crate git2;
+extern crate glob;
+extern crate terminal_size;
+extern crate users;
 
 mod cli;
+mod color;
 mod file;
 mod flags;
+mod git;
+mod parallel;
+mod size;
 
+use color::Palette;
 use file::File;
 use flags::Flags;
+use git::GitCache;
 
 fn main() -> io::Result<()> {
     let matches = cli::create_app().get_matches();
@@ -27,72 +39,41 @@ fn main() -> io::Result<()> {
 
     let mut writer: Box<dyn Write> = Box::new(io::stdout());
 
+    let is_tty = atty::is(atty::Stream::Stdout);
+
+    let palette = Palette::new(flags.color, is_tty);
+
     let multiple = files.len() > 1;
 
     for file in files {
-        match fs::read_dir(file) {
-            Ok(dir) => {
-                let mut dir: Vec<_> = dir
-                    // Collect information about the file or directory
-                    .map(|entry| File::from(entry.unwrap().path(), flags).unwrap())
-                    // Hide hidden files and directories if `-a` or `-A` flags
-                    // weren't provided
-                    .filter(|file| !File::is_hidden(&file.name) || flags.show_hidden())
-                    .collect();
-
-                if !flags.no_sort {
-                    if flags.time {
-                        if flags.last_accessed {
-                            dir.sort_by_key(sort_by_access_time);
-                        } else {
-                            dir.sort_by_key(sort_by_time);
-                        }
-                        dir.reverse();
-                    } else if flags.sort_size {
-                        dir.sort_by_key(sort_by_size);
-                        dir.reverse();
-                    } else {
-                        // Sort the directory entries by file name by default
-                        dir.sort_by_key(sort_by_name);
-                    }
-
-                    if flags.reverse {
-                        dir.reverse();
-                    }
-                }
-
-                if flags.all || flags.no_sort {
-                    // Retrieve the current directories information. This must
-                    // be canonicalize incase the path is relative
-                    let current = path::PathBuf::from(file).canonicalize().unwrap();
-
-                    let dot = File::from_name(".".to_string(), current.clone(), flags)
-                        .expect("Failed to read .");
-
-                    // Retrieve the parent path. Default to the current path if the parent doesn't
-                    // exist
-                    let parent_path =
-                        path::PathBuf::from(dot.path.parent().unwrap_or_else(|| current.as_path()));
-
-                    let dot_dot = File::from_name("..".to_string(), parent_path, flags)
-                        .expect("Failed to read ..");
-
-                    dir.insert(0, dot);
-                    dir.insert(1, dot_dot);
-                }
-
-                if multiple {
-                    writeln!(writer, "\n{}:", file)?;
-                }
-
-                if !flags.comma_separate && flags.show_list() {
-                    if print_list(dir, &mut writer, flags).is_err() {
-                        exit_code = 1
-                    }
-                } else if print_default(dir, &mut writer, flags).is_err() {
+        if flags.tree {
+            match print_tree(Path::new(file), file, flags.clone(), &palette, &mut writer, 0, "") {
+                Ok(code) if code != 0 => exit_code = code,
+                Ok(_) => {},
+                Err(err) => {
+                    eprintln!("ls: cannot access '{}': {}", file, err);
                     exit_code = 1;
-                }
-            },
+                },
+            }
+
+            continue;
+        }
+
+        let show_header = multiple || flags.recursive;
+        let git_cache = if flags.git { GitCache::for_directory(Path::new(file), flags.all) } else { None };
+
+        match list_directory(
+            Path::new(file),
+            flags.clone(),
+            &palette,
+            &mut writer,
+            show_header,
+            0,
+            is_tty,
+            git_cache.as_ref(),
+        ) {
+            Ok(code) if code != 0 => exit_code = code,
+            Ok(_) => {},
             Err(err) => {
                 eprintln!("ls: cannot access '{}': {}", file, err);
                 exit_code = 1;
@@ -107,18 +88,279 @@ fn main() -> io::Result<()> {
     Ok(())
 }
 
-/// Prints information about a file in the default format
-fn print_default<W: Write>(files: Vec<File>, writer: &mut W, flags: Flags) -> io::Result<()> {
-    for file in files {
-        let file_name = file.file_name();
+/// Read, filter and sort the entries of a single directory, inserting `.`
+/// and `..` when `-a` or `-f` is in effect.
+fn read_entries(path: &Path, flags: Flags) -> io::Result<Vec<File>> {
+    let paths: Vec<_> = fs::read_dir(path)?.map(|entry| entry.unwrap().path()).collect();
+    let threads = flags.threads;
+
+    let mut dir: Vec<_> = parallel::gather(paths, flags.clone(), threads)
+        .into_iter()
+        // Hide hidden files and directories, entries matching `--ignore`
+        // (unless `-a`/`-A`), and anything matching `--exclude`.
+        .filter(|file| {
+            (!File::is_hidden(&file.name) || flags.show_hidden())
+                && (flags.show_hidden() || !flags.is_ignored(&file.name))
+                && !flags.is_excluded(&file.name)
+        })
+        .collect();
+
+    if !flags.no_sort {
+        if flags.time {
+            if flags.last_accessed {
+                dir.sort_by_key(sort_by_access_time);
+            } else {
+                dir.sort_by_key(sort_by_time);
+            }
+            dir.reverse();
+        } else if flags.sort_size {
+            dir.sort_by_key(sort_by_size);
+            dir.reverse();
+        } else {
+            // Sort the directory entries by file name by default
+            dir.sort_by_key(sort_by_name);
+        }
+
+        if flags.reverse {
+            dir.reverse();
+        }
+    }
+
+    if flags.all || flags.no_sort {
+        // Retrieve the current directories information. This must
+        // be canonicalize incase the path is relative
+        let current = path.canonicalize().unwrap();
+
+        let dot = File::from_name(".".to_string(), current.clone(), flags.clone())
+            .expect("Failed to read .");
+
+        // Retrieve the parent path. Default to the current path if the parent doesn't
+        // exist
+        let parent_path =
+            path::PathBuf::from(dot.path.parent().unwrap_or_else(|| current.as_path()));
+
+        let dot_dot =
+            File::from_name("..".to_string(), parent_path, flags).expect("Failed to read ..");
+
+        dir.insert(0, dot);
+        dir.insert(1, dot_dot);
+    }
+
+    Ok(dir)
+}
+
+/// Print a single directory, then (when `-R`/`--recursive` is set and within
+/// `--depth`) descend into each of its subdirectories. `git_cache` is built
+/// once by the caller for the top-level path and threaded down through
+/// recursive calls, rather than re-walking the repository's status for every
+/// subdirectory visited.
+fn list_directory<W: Write>(
+    path: &Path,
+    flags: Flags,
+    palette: &Palette,
+    writer: &mut W,
+    show_header: bool,
+    depth: usize,
+    is_tty: bool,
+    git_cache: Option<&GitCache>,
+) -> io::Result<i32> {
+    let dir = read_entries(path, flags.clone())?;
 
-        if flags.comma_separate {
-            write!(writer, "{}, ", file_name)?;
+    let mut exit_code = 0;
+
+    if show_header {
+        writeln!(writer, "\n{}:", path.display())?;
+    }
+
+    if !flags.comma_separate && flags.show_list() {
+        if print_list(&dir, writer, flags.clone(), palette, git_cache).is_err() {
+            exit_code = 1;
+        }
+    } else if print_default(&dir, writer, flags.clone(), palette, is_tty).is_err() {
+        exit_code = 1;
+    }
+
+    let descend = flags.recursive && flags.depth.map_or(true, |max_depth| depth < max_depth);
+
+    if descend {
+        for entry in &dir {
+            if entry.name == "." || entry.name == ".." || !entry.metadata.is_dir() {
+                continue;
+            }
+
+            match list_directory(&entry.path, flags.clone(), palette, writer, true, depth + 1, is_tty, git_cache) {
+                Ok(code) if code != 0 => exit_code = code,
+                Ok(_) => {},
+                Err(err) => {
+                    eprintln!("ls: cannot access '{}': {}", entry.path.display(), err);
+                    exit_code = 1;
+                },
+            }
+        }
+    }
+
+    Ok(exit_code)
+}
+
+/// Print `path` and, within `--depth`, its contents as an indented tree
+/// using Unicode branch connectors.
+fn print_tree<W: Write>(
+    path: &Path,
+    label: &str,
+    flags: Flags,
+    palette: &Palette,
+    writer: &mut W,
+    depth: usize,
+    prefix: &str,
+) -> io::Result<i32> {
+    writeln!(writer, "{}", label)?;
+
+    let mut exit_code = 0;
+
+    if flags.depth.map_or(false, |max_depth| depth >= max_depth) {
+        return Ok(exit_code);
+    }
+
+    let entries: Vec<_> = read_entries(path, flags.clone())?
+        .into_iter()
+        .filter(|file| file.name != "." && file.name != "..")
+        .collect();
+
+    let count = entries.len();
+
+    for (index, entry) in entries.into_iter().enumerate() {
+        let is_last = index + 1 == count;
+        let connector = if is_last { "└── " } else { "├── " };
+        let child_prefix = format!("{}{}", prefix, if is_last { "   " } else { "│  " });
+
+        write!(writer, "{}{}", prefix, connector)?;
+
+        let label = entry.file_name(palette);
+
+        if entry.metadata.is_dir() {
+            match print_tree(&entry.path, &label, flags.clone(), palette, writer, depth + 1, &child_prefix) {
+                Ok(code) if code != 0 => exit_code = code,
+                Ok(_) => {},
+                Err(err) => {
+                    eprintln!("ls: cannot access '{}': {}", entry.path.display(), err);
+                    exit_code = 1;
+                },
+            }
         } else {
-            writeln!(writer, "{}", file_name)?;
+            writeln!(writer, "{}", label)?;
         }
     }
+
+    Ok(exit_code)
+}
+
+/// Prints information about a file in the default format: a GNU `ls`-style
+/// multi-column grid when writing to a terminal or when `-C` is given, or one
+/// entry per line otherwise and whenever `-1` is given.
+fn print_default<W: Write>(
+    files: &[File],
+    writer: &mut W,
+    flags: Flags,
+    palette: &Palette,
+    is_tty: bool,
+) -> io::Result<()> {
     if flags.comma_separate {
+        for file in files {
+            write!(writer, "{}, ", file.file_name(palette))?;
+        }
+        writeln!(writer)?;
+
+        return Ok(());
+    }
+
+    if !flags.one_per_line && (flags.columns || is_tty) {
+        return print_grid(files, writer, flags, palette);
+    }
+
+    for file in files {
+        writeln!(writer, "{}", file.file_name(palette))?;
+    }
+
+    Ok(())
+}
+
+/// Lay `files` out in a GNU `ls`-style grid: the widest number of columns
+/// that fits the terminal, filled column-major (down then across) by
+/// default, or row-major when `-x` is given.
+fn print_grid<W: Write>(files: &[File], writer: &mut W, flags: Flags, palette: &Palette) -> io::Result<()> {
+    if files.is_empty() {
+        return Ok(());
+    }
+
+    const SPACING: usize = 2;
+
+    let term_width = terminal_size::terminal_size().map(|(width, _)| width.0 as usize).unwrap_or(80);
+
+    let widths: Vec<usize> = files.iter().map(|file| file.name.chars().count()).collect();
+
+    // No column count above this can possibly fit: every column is at least
+    // as wide as the narrowest entry, so this is the largest `columns` for
+    // which `columns * min_width + SPACING * (columns - 1) <= term_width`
+    // can hold. Starting the search here instead of at `files.len()` avoids
+    // recomputing candidate widths for column counts that are guaranteed to
+    // overflow the terminal, which matters for large flat directories.
+    let min_width = widths.iter().copied().min().unwrap_or(0);
+    let max_columns = (term_width + SPACING) / (min_width + SPACING);
+
+    let mut columns = max_columns.min(files.len()).max(1);
+    let mut column_widths: Vec<usize> = Vec::new();
+
+    while columns > 1 {
+        let rows = (files.len() + columns - 1) / columns;
+        let mut candidate = vec![0; columns];
+
+        for (index, width) in widths.iter().enumerate() {
+            let column = if flags.across { index % columns } else { index / rows };
+
+            if *width > candidate[column] {
+                candidate[column] = *width;
+            }
+        }
+
+        let total = candidate.iter().sum::<usize>() + SPACING * (columns - 1);
+
+        if total <= term_width {
+            column_widths = candidate;
+            break;
+        }
+
+        columns -= 1;
+    }
+
+    if columns <= 1 {
+        for file in files {
+            writeln!(writer, "{}", file.file_name(palette))?;
+        }
+
+        return Ok(());
+    }
+
+    let rows = (files.len() + columns - 1) / columns;
+
+    for row in 0..rows {
+        for column in 0..columns {
+            let index = if flags.across { row * columns + column } else { column * rows + row };
+
+            if index >= files.len() {
+                break;
+            }
+
+            write!(writer, "{}", files[index].file_name(palette))?;
+
+            let last_in_row = column + 1 == columns
+                || if flags.across { index + 1 == files.len() } else { index + rows >= files.len() };
+
+            if !last_in_row {
+                let padding = column_widths[column].saturating_sub(widths[index]) + SPACING;
+                write!(writer, "{}", " ".repeat(padding))?;
+            }
+        }
+
         writeln!(writer)?;
     }
 
@@ -126,7 +368,13 @@ fn print_default<W: Write>(files: Vec<File>, writer: &mut W, flags: Flags) -> io
 }
 
 /// Prints information about the provided file in the long (`-l`) format
-fn print_list<W: Write>(files: Vec<File>, writer: &mut W, flags: Flags) -> io::Result<()> {
+fn print_list<W: Write>(
+    files: &[File],
+    writer: &mut W,
+    flags: Flags,
+    palette: &Palette,
+    git_cache: Option<&GitCache>,
+) -> io::Result<()> {
     let mut inode_width = 1;
     let mut block_width = 1;
     let mut hard_links_width = 1;
@@ -134,7 +382,7 @@ fn print_list<W: Write>(files: Vec<File>, writer: &mut W, flags: Flags) -> io::R
     let mut group_width = 1;
     let mut size_width = 1;
 
-    for file in &files {
+    for file in files {
         if flags.inode {
             let inode = file.inode().len();
 
@@ -198,7 +446,13 @@ fn print_list<W: Write>(files: Vec<File>, writer: &mut W, flags: Flags) -> io::R
         }
     }
 
-    for file in &files {
+    for file in files {
+        if let Some(git_cache) = git_cache {
+            let status = git_cache.status(&file.path).unwrap_or("--");
+
+            write!(writer, "{} ", status)?;
+        }
+
         if flags.inode {
             write!(
                 writer,
@@ -253,7 +507,7 @@ fn print_list<W: Write>(files: Vec<File>, writer: &mut W, flags: Flags) -> io::R
 
         write!(writer, "{} ", file.time()?)?;
 
-        write!(writer, "{}", file.file_name())?;
+        write!(writer, "{}", file.file_name(palette))?;
 
         writeln!(writer)?;
     }
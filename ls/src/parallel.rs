@@ -0,0 +1,33 @@
+use std::path::PathBuf;
+use std::thread;
+
+use crate::file::File;
+use crate::flags::Flags;
+
+/// Gather file metadata for `paths`, splitting the `stat` calls across
+/// `threads` worker threads. Falls back to a single-threaded pass when
+/// `threads <= 1` or there isn't enough work to make splitting worthwhile;
+/// sorting happens afterwards so output stays deterministic either way.
+pub(crate) fn gather(paths: Vec<PathBuf>, flags: Flags, threads: usize) -> Vec<File> {
+    if threads <= 1 || paths.len() < threads * 2 {
+        return paths.into_iter().map(|path| File::from(path, flags.clone()).unwrap()).collect();
+    }
+
+    let chunk_size = (paths.len() + threads - 1) / threads;
+    let chunks: Vec<Vec<PathBuf>> = paths.chunks(chunk_size).map(<[PathBuf]>::to_vec).collect();
+
+    thread::scope(|scope| {
+        let handles: Vec<_> = chunks
+            .into_iter()
+            .map(|chunk| {
+                let flags = flags.clone();
+
+                scope.spawn(move || {
+                    chunk.into_iter().map(|path| File::from(path, flags.clone()).unwrap()).collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        handles.into_iter().flat_map(|handle| handle.join().unwrap()).collect()
+    })
+}
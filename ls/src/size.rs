@@ -0,0 +1,76 @@
+/// How a file's size should be rendered.
+#[derive(Clone, Copy)]
+pub(crate) enum SizeFormat {
+    /// The raw byte count.
+    Bytes,
+    /// Scaled to the largest unit where the value is >= 1, using powers of
+    /// 1024 (`human_readable`) or 1000 (`si`).
+    HumanReadable { si: bool },
+    /// Divided by a fixed number of bytes per block, rounded up.
+    BlockSize(u64),
+}
+
+/// Parse a `--block-size=N[KMG]` argument into a byte count, e.g. `"1K"` ->
+/// `1024`, `"512"` -> `512`. Rejects `0`, which would otherwise divide by
+/// zero in [`format_block_size`].
+pub(crate) fn parse_block_size(value: &str) -> Option<u64> {
+    let (digits, suffix) = match value.chars().last() {
+        Some(c) if c.is_ascii_alphabetic() => (&value[..value.len() - 1], c.to_ascii_uppercase()),
+        _ => (value, '\0'),
+    };
+
+    let quantity: u64 = digits.parse().ok()?;
+
+    let multiplier = match suffix {
+        'K' => 1024,
+        'M' => 1024 * 1024,
+        'G' => 1024 * 1024 * 1024,
+        '\0' => 1,
+        _ => return None,
+    };
+
+    if quantity == 0 {
+        return None;
+    }
+
+    Some(quantity * multiplier)
+}
+
+/// Scale `bytes` to the largest unit where the value is >= 1, printing one
+/// decimal place below 10 and none otherwise (e.g. `1.0K`, `234M`, `2.3G`).
+/// Sizes under the first unit are printed as a plain byte count.
+pub(crate) fn format_human_readable(bytes: u64, si: bool) -> String {
+    const UNITS: [&str; 5] = ["K", "M", "G", "T", "P"];
+
+    let base: f64 = if si { 1000.0 } else { 1024.0 };
+    let mut value = bytes as f64;
+    let mut unit = None;
+
+    for name in &UNITS {
+        if value < base {
+            break;
+        }
+        value /= base;
+        unit = Some(*name);
+    }
+
+    match unit {
+        None => bytes.to_string(),
+        Some(name) if value < 10.0 => format!("{:.1}{}", value, name),
+        Some(name) => format!("{:.0}{}", value, name),
+    }
+}
+
+/// Divide `bytes` by `block_size`, rounding up, and return the plain count
+/// of blocks.
+pub(crate) fn format_block_size(bytes: u64, block_size: u64) -> String {
+    ((bytes + block_size - 1) / block_size).to_string()
+}
+
+pub(crate) fn format_size(bytes: u64, format: SizeFormat) -> String {
+    match format {
+        SizeFormat::Bytes => bytes.to_string(),
+        SizeFormat::HumanReadable { si } => format_human_readable(bytes, si),
+        SizeFormat::BlockSize(block_size) => format_block_size(bytes, block_size),
+    }
+}